@@ -1,4 +1,4 @@
-use soroban_sdk::{contract, contractimpl, Address, Env, Vec, Map, String, BytesN, panic_with_error};
+use soroban_sdk::{contract, contractimpl, token, Address, Env, Vec, Map, String, BytesN, Bytes, Symbol, Val, IntoVal, vec, panic_with_error};
 use crate::types::*;
 use crate::event;
 
@@ -30,14 +30,20 @@ impl NFTCollection {
         }
 
         _set_collection_info(
-            &env, 
+            &env,
             &collection_info
         );
 
+        env.storage().instance().set(&DataKey::Version, &VERSION);
+
         event::initialized(&env);
     }
 
     pub fn transfer(env: Env, from: Address, to: Address, token_id: u32) {
+        if env.storage().instance().has(&DataKey::Paused) {
+            panic_with_error!(&env, Error::Paused)
+        }
+
         let mut token: TokenInfo = env.storage().persistent().get(&DataKey::Token(token_id)).unwrap_or_else(|| panic_with_error!(&env, Error::NotNFT));
 
         from.require_auth();
@@ -52,10 +58,69 @@ impl NFTCollection {
         event::transfer(&env, token_id, to);
     }
 
-    pub fn mint(env: Env, owner: Address, token_id: u32, token_uri: String) {
+    /// Moves `token_id` into `to_contract` and synchronously notifies it by
+    /// invoking its well-known `on_nft_received` entrypoint. Soroban calls
+    /// are synchronous, so there is no async resolver step: if the receiver
+    /// returns `false` or traps, the whole transaction reverts and `from`
+    /// stays the owner. While `on_nft_received` is executing, `to_contract`
+    /// already sees itself as the owner of `token_id`, so it may
+    /// immediately re-list or escrow the token from within that callback.
+    pub fn transfer_call(env: Env, from: Address, to_contract: Address, token_id: u32, msg: Bytes) {
+        if env.storage().instance().has(&DataKey::Paused) {
+            panic_with_error!(&env, Error::Paused)
+        }
+
+        let mut token: TokenInfo = env.storage().persistent().get(&DataKey::Token(token_id)).unwrap_or_else(|| panic_with_error!(&env, Error::NotNFT));
+
+        from.require_auth();
+
+        token.check_can_send(&env, from.clone());
+
+        token.owner = to_contract.clone();
+        token.approvals = Map::new(&env);
+
+        _set_token_info(&env, token_id, &token);
+
+        // (operator, from, token_id, msg) per the on_nft_received interface;
+        // operator and from are both `from` since transfer_call has no
+        // separate approved-spender caller.
+        let args: Vec<Val> = vec![
+            &env,
+            from.clone().into_val(&env),
+            from.clone().into_val(&env),
+            token_id.into_val(&env),
+            msg.into_val(&env),
+        ];
+
+        let accepted: bool = env.invoke_contract(&to_contract, &Symbol::new(&env, "on_nft_received"), args);
+
+        if !accepted {
+            panic_with_error!(&env, Error::TransferRejected);
+        }
+
+        // Guard against the receiver re-entering `transfer_call` (or
+        // `transfer`) on this token from within its own callback.
+        let confirmed: TokenInfo = env.storage().persistent().get(&DataKey::Token(token_id)).unwrap_or_else(|| panic_with_error!(&env, Error::NotNFT));
+
+        if confirmed.owner != to_contract {
+            panic_with_error!(&env, Error::TransferRejected);
+        }
+
+        event::transfer_call(&env, token_id, to_contract);
+    }
+
+    pub fn mint(env: Env, minter: Address, owner: Address, token_id: u32, token_uri: String) {
+        if env.storage().instance().has(&DataKey::Paused) {
+            panic_with_error!(&env, Error::Paused)
+        }
+
         let collection_info: CollectionInfo =  env.storage().instance().get(&DataKey::CollectionInfo).unwrap_or_else(|| panic_with_error!(&env, Error::NotInitialized));
 
-        collection_info.minter.require_auth();
+        minter.require_auth();
+
+        if minter != collection_info.minter && !_has_role(&env, &minter, Role::Minter) {
+            panic_with_error!(&env, Error::NotAuthorized);
+        }
 
         if env.storage().persistent().has(&DataKey::Token(token_id)) {
             panic_with_error!(&env, Error::AlreadyMinted);
@@ -80,10 +145,18 @@ impl NFTCollection {
         event::mint(&env, owner, token_id);
     }
 
-    pub fn bulk_mint(env: Env, owner: Address, tokens: Vec<(u32, String)>) {
+    pub fn bulk_mint(env: Env, minter: Address, owner: Address, tokens: Vec<(u32, String)>) {
+        if env.storage().instance().has(&DataKey::Paused) {
+            panic_with_error!(&env, Error::Paused)
+        }
+
         let collection_info: CollectionInfo =  env.storage().instance().get(&DataKey::CollectionInfo).unwrap_or_else(|| panic_with_error!(&env, Error::NotInitialized));
 
-        collection_info.minter.require_auth();
+        minter.require_auth();
+
+        if minter != collection_info.minter && !_has_role(&env, &minter, Role::Minter) {
+            panic_with_error!(&env, Error::NotAuthorized);
+        }
 
         let mut tokens_count:u32 = env.storage().instance().get(&DataKey::TokensCount).unwrap_or(0);
 
@@ -177,26 +250,315 @@ impl NFTCollection {
         env.storage().temporary().remove(&DataKey::Operator(owner.clone(), operator.clone()));
     }
 
-    // Actions
+    // Access control
+
+    pub fn grant_role(env: Env, caller: Address, account: Address, role: Role) {
+        caller.require_auth();
+
+        _require_admin(&env, &caller);
+
+        let mut roles: Vec<Role> = env.storage().persistent().get(&DataKey::Role(account.clone())).unwrap_or(Vec::new(&env));
+
+        if !roles.contains(&role) {
+            roles.push_back(role.clone());
+
+            env.storage().persistent().set(&DataKey::Role(account.clone()), &roles);
+            env.storage().persistent().extend_ttl(&DataKey::Role(account.clone()), PERSISTENT_LIFETIME_THRESHOLD, PERSISTENT_BUMP_AMOUNT);
+        }
+
+        event::role_granted(&env, account, role);
+    }
+
+    pub fn revoke_role(env: Env, caller: Address, account: Address, role: Role) {
+        caller.require_auth();
+
+        _require_admin(&env, &caller);
+
+        let roles: Vec<Role> = env.storage().persistent().get(&DataKey::Role(account.clone())).unwrap_or(Vec::new(&env));
+
+        let mut remaining: Vec<Role> = Vec::new(&env);
+
+        for existing in roles.iter() {
+            if existing != role {
+                remaining.push_back(existing);
+            }
+        }
+
+        env.storage().persistent().set(&DataKey::Role(account.clone()), &remaining);
+
+        event::role_revoked(&env, account, role);
+    }
+
+    /// Reversibly halts every entrypoint that moves NFT ownership
+    /// (`transfer`, `mint`, `bulk_mint`, `transfer_call`, `buy`,
+    /// `claim_swap`) so a `Pauser` can respond to an exploit without
+    /// bricking the collection the way `freeze_collection` does. Distinct
+    /// from `Frozen`, which is never lifted and blocks metadata updates,
+    /// upgrades and new listings rather than transfers.
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+
+        _require_pauser(&env, &caller);
+
+        env.storage().instance().set(&DataKey::Paused, &true);
+
+        event::paused(&env);
+    }
+
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+
+        _require_pauser(&env, &caller);
+
+        env.storage().instance().remove(&DataKey::Paused);
+
+        event::unpaused(&env);
+    }
+
+    // Marketplace
+
+    pub fn list(env: Env, seller: Address, token_id: u32, price: i128, pay_token: Address, expires: Option<Expiration>) {
+        if env.storage().instance().has(&DataKey::Frozen) {
+            panic_with_error!(&env, Error::Frozen)
+        }
+
+        if price <= 0 {
+            panic_with_error!(&env, Error::InvalidPrice)
+        }
+
+        let mut token: TokenInfo = env.storage().persistent().get(&DataKey::Token(token_id)).unwrap_or_else(|| panic_with_error!(&env, Error::NotNFT));
+
+        seller.require_auth();
+
+        token.check_can_approve(&env, seller.clone());
+
+        // The collection contract itself is recorded as an approved spender
+        // so `buy` can move the NFT on sale without a separate `approve` call.
+        let current_contract = env.current_contract_address();
+        token.approvals = _update_approvals(&env, token.clone(), current_contract, true, expires.clone());
+
+        _set_token_info(&env, token_id, &token);
+
+        let listing = Listing {
+            seller: seller.clone(),
+            price,
+            pay_token,
+            expires,
+        };
+
+        env.storage().persistent().set(&DataKey::Listing(token_id), &listing);
+        env.storage().persistent().extend_ttl(&DataKey::Listing(token_id), PERSISTENT_LIFETIME_THRESHOLD, PERSISTENT_BUMP_AMOUNT);
+
+        event::listed(&env, token_id, seller, price);
+    }
+
+    pub fn unlist(env: Env, seller: Address, token_id: u32) {
+        let listing: Listing = env.storage().persistent().get(&DataKey::Listing(token_id)).unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+
+        seller.require_auth();
+
+        if listing.seller != seller {
+            panic_with_error!(&env, Error::NotAuthorized);
+        }
+
+        // Revoke the self-approval `list` recorded so the collection
+        // contract can no longer move this token once the listing is gone.
+        let mut token: TokenInfo = env.storage().persistent().get(&DataKey::Token(token_id)).unwrap_or_else(|| panic_with_error!(&env, Error::NotNFT));
+        let current_contract = env.current_contract_address();
+        token.approvals = _update_approvals(&env, token.clone(), current_contract, false, None);
+        _set_token_info(&env, token_id, &token);
+
+        env.storage().persistent().remove(&DataKey::Listing(token_id));
+
+        event::unlisted(&env, token_id);
+    }
+
+    pub fn buy(env: Env, buyer: Address, token_id: u32) {
+        if env.storage().instance().has(&DataKey::Paused) {
+            panic_with_error!(&env, Error::Paused)
+        }
+
+        let listing: Listing = env.storage().persistent().get(&DataKey::Listing(token_id)).unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+
+        if listing.expires.is_some() && listing.expires.clone().unwrap().is_expired(&env) {
+            panic_with_error!(&env, Error::Expired);
+        }
+
+        buyer.require_auth();
+
+        // Re-validate current ownership in case the NFT moved since listing.
+        let mut token: TokenInfo = env.storage().persistent().get(&DataKey::Token(token_id)).unwrap_or_else(|| panic_with_error!(&env, Error::NotNFT));
+
+        if token.owner != listing.seller {
+            panic_with_error!(&env, Error::NotOwner);
+        }
 
-    pub fn freeze_collection(env: Env) {
         let collection_info: CollectionInfo = env.storage().instance().get(&DataKey::CollectionInfo).unwrap_or_else(|| panic_with_error!(&env, Error::NotInitialized));
 
-        collection_info.admin.require_auth();
+        let pay_token_client = token::Client::new(&env, &listing.pay_token);
+
+        if let Some(royalty_info) = collection_info.royalty_info {
+            let royalty_amount = listing.price * (royalty_info.share as i128) / 10000;
+            let seller_amount = listing.price - royalty_amount;
+
+            if royalty_amount > 0 {
+                pay_token_client.transfer(&buyer, &royalty_info.payment_address, &royalty_amount);
+            }
+
+            pay_token_client.transfer(&buyer, &listing.seller, &seller_amount);
+        } else {
+            pay_token_client.transfer(&buyer, &listing.seller, &listing.price);
+        }
+
+        token.owner = buyer.clone();
+        token.approvals = Map::new(&env);
+
+        _set_token_info(&env, token_id, &token);
+
+        env.storage().persistent().remove(&DataKey::Listing(token_id));
+
+        event::sale(&env, token_id, listing.seller, buyer, listing.price);
+    }
+
+    // Swaps
+
+    /// Approves the collection contract as spender of `offered_token` (like
+    /// `list` does; the maker stays its owner, and `claim_swap` fails if the
+    /// token has since moved) and records an offer to trade it for
+    /// `wanted_token`, `wanted_price`, or both. Keyed by `offered_token`
+    /// since a token can only be offered in one swap at a time.
+    pub fn create_swap(env: Env, maker: Address, offered_token: u32, wanted_token: Option<u32>, wanted_price: Option<(Address, i128)>, deadline: Expiration) {
+        if wanted_token.is_none() && wanted_price.is_none() {
+            panic_with_error!(&env, Error::InvalidSwap)
+        }
+
+        if env.storage().persistent().has(&DataKey::Swap(offered_token)) {
+            panic_with_error!(&env, Error::AlreadySwapped)
+        }
+
+        let mut token: TokenInfo = env.storage().persistent().get(&DataKey::Token(offered_token)).unwrap_or_else(|| panic_with_error!(&env, Error::NotNFT));
+
+        maker.require_auth();
+
+        token.check_can_approve(&env, maker.clone());
+
+        let current_contract = env.current_contract_address();
+        token.approvals = _update_approvals(&env, token.clone(), current_contract, true, Some(deadline.clone()));
+
+        _set_token_info(&env, offered_token, &token);
+
+        let swap = Swap {
+            maker: maker.clone(),
+            offered_token,
+            wanted_token,
+            wanted_price,
+            deadline,
+        };
+
+        env.storage().persistent().set(&DataKey::Swap(offered_token), &swap);
+        env.storage().persistent().extend_ttl(&DataKey::Swap(offered_token), PERSISTENT_LIFETIME_THRESHOLD, PERSISTENT_BUMP_AMOUNT);
+
+        event::swap_created(&env, offered_token, maker);
+    }
+
+    pub fn cancel_swap(env: Env, maker: Address, swap_id: u32) {
+        let swap: Swap = env.storage().persistent().get(&DataKey::Swap(swap_id)).unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+
+        maker.require_auth();
+
+        if swap.maker != maker {
+            panic_with_error!(&env, Error::NotOwner);
+        }
+
+        _revoke_swap_approval(&env, swap.offered_token);
+
+        env.storage().persistent().remove(&DataKey::Swap(swap_id));
+
+        event::swap_cancelled(&env, swap_id);
+    }
+
+    pub fn claim_swap(env: Env, taker: Address, swap_id: u32) {
+        if env.storage().instance().has(&DataKey::Paused) {
+            panic_with_error!(&env, Error::Paused)
+        }
+
+        let swap: Swap = env.storage().persistent().get(&DataKey::Swap(swap_id)).unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+
+        if swap.deadline.is_expired(&env) {
+            panic_with_error!(&env, Error::Expired);
+        }
+
+        taker.require_auth();
+
+        let mut offered: TokenInfo = env.storage().persistent().get(&DataKey::Token(swap.offered_token)).unwrap_or_else(|| panic_with_error!(&env, Error::NotNFT));
+
+        if offered.owner != swap.maker {
+            panic_with_error!(&env, Error::NotOwner);
+        }
+
+        if let Some(wanted_token) = swap.wanted_token {
+            let mut wanted: TokenInfo = env.storage().persistent().get(&DataKey::Token(wanted_token)).unwrap_or_else(|| panic_with_error!(&env, Error::NotNFT));
+
+            if wanted.owner != taker {
+                panic_with_error!(&env, Error::NotOwner);
+            }
+
+            wanted.owner = swap.maker.clone();
+            wanted.approvals = Map::new(&env);
+            _set_token_info(&env, wanted_token, &wanted);
+        }
+
+        if let Some((pay_token, price)) = swap.wanted_price {
+            let token_client = token::Client::new(&env, &pay_token);
+            token_client.transfer(&taker, &swap.maker, &price);
+        }
+
+        offered.owner = taker.clone();
+        offered.approvals = Map::new(&env);
+        _set_token_info(&env, swap.offered_token, &offered);
+
+        env.storage().persistent().remove(&DataKey::Swap(swap_id));
+
+        event::swap_claimed(&env, swap_id, taker);
+    }
+
+    /// Anyone may sweep a swap whose `deadline` has passed, reclaiming its
+    /// storage, mirroring the rule that an expired approval may be cleared
+    /// by anyone.
+    pub fn sweep_expired_swap(env: Env, swap_id: u32) {
+        let swap: Swap = env.storage().persistent().get(&DataKey::Swap(swap_id)).unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+
+        if !swap.deadline.is_expired(&env) {
+            panic_with_error!(&env, Error::NotExpired);
+        }
+
+        _revoke_swap_approval(&env, swap.offered_token);
+
+        env.storage().persistent().remove(&DataKey::Swap(swap_id));
+
+        event::swap_cancelled(&env, swap_id);
+    }
+
+    // Actions
+
+    pub fn freeze_collection(env: Env, caller: Address) {
+        caller.require_auth();
+
+        _require_admin(&env, &caller);
 
         env.storage().instance().set(&DataKey::Frozen, &true);
 
         event::freeze(&env);
     }
 
-    pub fn update_token_url(env: Env, token_id: u32, token_uri: String) {
+    pub fn update_token_url(env: Env, caller: Address, token_id: u32, token_uri: String) {
         if env.storage().instance().has(&DataKey::Frozen) {
             panic_with_error!(&env, Error::Frozen)
         }
 
-        let collection_info: CollectionInfo = env.storage().instance().get(&DataKey::CollectionInfo).unwrap_or_else(|| panic_with_error!(&env, Error::NotInitialized));
-        
-        collection_info.admin.require_auth();
+        caller.require_auth();
+
+        _require_admin(&env, &caller);
 
         let mut token: TokenInfo = env.storage().persistent().get(&DataKey::Token(token_id)).unwrap_or_else(|| panic_with_error!(&env, Error::NotNFT));
 
@@ -208,16 +570,19 @@ impl NFTCollection {
     }
 
     pub fn update_collection_info(
-        env: Env, 
+        env: Env,
+        caller: Address,
         new_collection_info: CollectionInfo
     ) {
         if env.storage().instance().has(&DataKey::Frozen) {
             panic_with_error!(&env, Error::Frozen)
         }
 
+        caller.require_auth();
+
+        _require_admin(&env, &caller);
+
         let collection_info: CollectionInfo = env.storage().instance().get(&DataKey::CollectionInfo).unwrap_or_else(|| panic_with_error!(&env, Error::NotInitialized));
-        
-        collection_info.admin.require_auth();
 
         if collection_info.minter != new_collection_info.minter {
             if env.storage().instance().has(&DataKey::MinterFrozen) {
@@ -237,20 +602,46 @@ impl NFTCollection {
         event::collection_updated(&env);
     }
 
-    pub fn upgrade(env: Env, hash: BytesN<32>) {
+    pub fn upgrade(env: Env, caller: Address, hash: BytesN<32>) {
         if env.storage().instance().has(&DataKey::Frozen) {
             panic_with_error!(&env, Error::Frozen)
         }
 
-        let collection_info: CollectionInfo = env.storage().instance().get(&DataKey::CollectionInfo).unwrap_or_else(|| panic_with_error!(&env, Error::NotInitialized));
-        
-        collection_info.admin.require_auth();
+        caller.require_auth();
+
+        _require_admin(&env, &caller);
 
         env.deployer().update_current_contract_wasm(hash.clone());
 
         event::upgraded(&env, hash);
     }
 
+    /// Runs after `upgrade` swaps the WASM. Migrates stored state to match
+    /// the new code's `VERSION`. Idempotent in the sense that it only ever
+    /// performs a given version's transformation once: if the persisted
+    /// version already matches `VERSION` the call reverts with
+    /// `Error::AlreadyMigrated` instead of silently succeeding, so callers
+    /// must check `version()` first rather than invoking this blindly.
+    pub fn migrate(env: Env, admin: Address) {
+        admin.require_auth();
+
+        _require_admin(&env, &admin);
+
+        let old_version: u32 = env.storage().instance().get(&DataKey::Version).unwrap_or(0);
+
+        if old_version == VERSION {
+            panic_with_error!(&env, Error::AlreadyMigrated);
+        }
+
+        // Per-version state transformations (re-keying or back-filling new
+        // `TokenInfo` fields) go here as the schema evolves. There is
+        // nothing to back-fill yet for this version bump.
+
+        env.storage().instance().set(&DataKey::Version, &VERSION);
+
+        event::migrated(&env, old_version, VERSION);
+    }
+
     pub fn extend_ttl_collection(env: Env, start_after: u32, limit: u32) {
         env.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
@@ -338,11 +729,48 @@ impl NFTCollection {
         env.storage().instance().get(&DataKey::TokensCount).unwrap_or(0)
     }
 
-    pub fn version() -> u32 {
-        VERSION
+    /// Returns the persisted on-chain version rather than the compiled-in
+    /// `VERSION` constant, so off-chain tooling can detect a contract that
+    /// has been upgraded but not yet migrated.
+    pub fn version(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::Version).unwrap_or(0)
     }
 }
 
+fn _has_role(env: &Env, account: &Address, role: Role) -> bool {
+    let roles: Vec<Role> = env.storage().persistent().get(&DataKey::Role(account.clone())).unwrap_or(Vec::new(env));
+
+    roles.contains(&role)
+}
+
+fn _require_admin(env: &Env, account: &Address) {
+    let collection_info: CollectionInfo = env.storage().instance().get(&DataKey::CollectionInfo).unwrap_or_else(|| panic_with_error!(env, Error::NotInitialized));
+
+    if *account != collection_info.admin && !_has_role(env, account, Role::Admin) {
+        panic_with_error!(env, Error::NotAuthorized);
+    }
+}
+
+fn _require_pauser(env: &Env, account: &Address) {
+    let collection_info: CollectionInfo = env.storage().instance().get(&DataKey::CollectionInfo).unwrap_or_else(|| panic_with_error!(env, Error::NotInitialized));
+
+    if *account != collection_info.admin && !_has_role(env, account, Role::Pauser) {
+        panic_with_error!(env, Error::NotAuthorized);
+    }
+}
+
+/// Revokes the collection contract's self-approval on `token_id`, mirroring
+/// the cleanup `unlist` does for marketplace listings — used when a swap is
+/// torn down (cancelled or swept) without the offered token changing hands.
+fn _revoke_swap_approval(env: &Env, token_id: u32) {
+    let mut token: TokenInfo = env.storage().persistent().get(&DataKey::Token(token_id)).unwrap_or_else(|| panic_with_error!(env, Error::NotNFT));
+
+    let current_contract = env.current_contract_address();
+    token.approvals = _update_approvals(env, token.clone(), current_contract, false, None);
+
+    _set_token_info(env, token_id, &token);
+}
+
 fn _change_tokens_count(env: &Env, decrease: bool) {
     let mut tokens_count:u32 = env.storage().instance().get(&DataKey::TokensCount).unwrap_or(0);
 